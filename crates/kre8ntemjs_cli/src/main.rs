@@ -7,36 +7,61 @@ use rand::thread_rng;
 use rand::Rng;
 use sha1::{Digest, Sha1};
 use regex::Regex;
-use kre8ntemjs_core::{Extractor, Mutator, Concretizer, Engine, load_seed_paths, read_to_string};
+use kre8ntemjs_core::{Extractor, Mutator, Concretizer, Engine, EngineSet, DiffRun, RunOutcome, Transformer, Corpus, is_syntax_error, score_with_regex, load_seed_paths, read_to_string};
+use kre8ntemjs_core::seed::{self, SeedMeta, SeedVerdict};
 
-fn crash_signature(stderr: &str) -> String {
-    // Normalize unstable bits (paths, line numbers, hex ptrs)
+/// Normalize unstable bits (`:line[:col]` suffixes, hex ptrs) shared by crash dedup and
+/// cross-engine diffing, so both compare on the same stable text.
+fn normalize_output(s: &str) -> String {
     let re_hex = Regex::new(r"0x[0-9a-fA-F]+").unwrap();
     let re_line = Regex::new(r":\d+(:\d+)?").unwrap();
-    let after_hex = re_hex.replace_all(stderr, "0xHEX");
-    let norm = re_line.replace_all(&after_hex, ":LINE");
+    let after_hex = re_hex.replace_all(s, "0xHEX");
+    re_line.replace_all(&after_hex, ":LINE").into_owned()
+}
+
+fn crash_signature(stderr: &str) -> String {
+    let norm = normalize_output(stderr);
     let mut h = Sha1::new();
     h.update(norm.as_bytes());
     format!("{:x}", h.finalize())
 }
 
-fn score_with_regex(out: &str, err: &str, re: &Regex) -> u64 {
-    let mut sum = 0u64;
-    for cap in re.captures_iter(out) {
-        for i in 1..cap.len() {
-            if let Ok(v) = cap[i].replace('_', "").parse::<u64>() {
-                sum += v;
-            }
-        }
-    }
-    for cap in re.captures_iter(err) {
-        for i in 1..cap.len() {
-            if let Ok(v) = cap[i].replace('_', "").parse::<u64>() {
-                sum += v;
-            }
-        }
+/// Save a divergent differential-testing case alongside a side-by-side report.
+fn write_diff_report(out_dir: &std::path::Path, iter: u64, prog: &str, runs: &[DiffRun]) -> anyhow::Result<()> {
+    let path = out_dir.join(format!("diff_iter{}_case.js", iter));
+    std::fs::write(&path, prog)?;
+
+    let mut report = String::new();
+    report.push_str("=== differential engine report ===\n");
+    for r in runs {
+        report.push_str(&format!(
+            "--- {} (exit={}) ---\nstdout:\n{}\nstderr:\n{}\n\n",
+            r.cmd, r.outcome.status, r.outcome.stdout, r.outcome.stderr
+        ));
     }
-    sum
+    let report_path = out_dir.join(format!("diff_iter{}_report.txt", iter));
+    std::fs::write(&report_path, report)?;
+    Ok(())
+}
+
+/// Save an optimizer-oracle mismatch: the original program, the rewritten variant, and
+/// a side-by-side report of what each one did.
+fn write_optimizer_report(
+    out_dir: &std::path::Path,
+    iter: u64,
+    orig_prog: &str,
+    transformed_prog: &str,
+    orig: &RunOutcome,
+    transformed: &RunOutcome,
+) -> anyhow::Result<()> {
+    std::fs::write(out_dir.join(format!("optbug_iter{}_orig.js", iter)), orig_prog)?;
+    std::fs::write(out_dir.join(format!("optbug_iter{}_transformed.js", iter)), transformed_prog)?;
+    let report = format!(
+        "=== optimizer-oracle mismatch ===\n--- original (exit={}) ---\nstdout:\n{}\nstderr:\n{}\n\n--- transformed (exit={}) ---\nstdout:\n{}\nstderr:\n{}\n",
+        orig.status, orig.stdout, orig.stderr, transformed.status, transformed.stdout, transformed.stderr
+    );
+    std::fs::write(out_dir.join(format!("optbug_iter{}_report.txt", iter)), report)?;
+    Ok(())
 }
 
 /// Simple CLI for the MVP fuzzer.
@@ -84,6 +109,21 @@ struct Args {
     /// Minimizer mode: "signature" or "coverage"
     #[arg(long, default_value="signature")]
     minimize_by: String,
+
+    /// Additional engine command to differentially test against the primary --engine-cmd
+    /// (repeatable, e.g. --diff-engine-cmd d8 --diff-engine-cmd jsc). Enables diff mode.
+    #[arg(long="diff-engine-cmd")]
+    diff_engine_cmd: Vec<String>,
+
+    /// Run the internal semantics-preserving optimizer-oracle: rewrite each program and
+    /// flag any behavioral difference between the original and the rewritten variant.
+    #[arg(long, default_value_t=false)]
+    optimizer_oracle: bool,
+
+    /// Directory of named harness snippets that seeds' Test262-style `includes:`
+    /// frontmatter can reference.
+    #[arg(long)]
+    harness_dir: Option<PathBuf>,
 }
 
 fn main() -> anyhow::Result<()> {
@@ -96,29 +136,65 @@ fn main() -> anyhow::Result<()> {
         timeout: Duration::from(args.timeout),
     };
 
-    let seeds = load_seed_paths(&args.seeds)?;
+    let diff_set = if args.diff_engine_cmd.is_empty() {
+        None
+    } else {
+        let mut engines = vec![eng.clone()];
+        engines.extend(args.diff_engine_cmd.iter().map(|cmd| Engine {
+            cmd: cmd.clone(),
+            args: Vec::new(),
+            timeout: eng.timeout,
+        }));
+        Some(EngineSet::new(engines))
+    };
+
+    // Coverage-guided corpus: resume a persisted queue from a prior campaign if one is
+    // sitting in --out, otherwise seed fresh from --seeds.
+    let corpus_path = args.out.join("corpus.json");
+    let mut corpus = match Corpus::load(&corpus_path)? {
+        Some(c) if !c.entries.is_empty() => c,
+        _ => {
+            let seed_paths = load_seed_paths(&args.seeds)?;
+            let mut srcs = Vec::with_capacity(seed_paths.len());
+            for p in &seed_paths {
+                srcs.push(read_to_string(p)?);
+            }
+            Corpus::from_seeds(srcs)
+        }
+    };
     let extractor = Extractor::default();
     let mut rng = thread_rng();
 
     let mut seen: HashSet<String> = HashSet::new();
+    let mut seen_conformance: HashSet<String> = HashSet::new();
+    // Resume coverage state from the loaded corpus too, or a resumed campaign would
+    // silently forget every score it already learned and restart the "keep only if it
+    // beats the global best" gate from zero.
+    let mut seen_scores: HashSet<u64> = corpus.entries.iter().map(|e| e.score).collect();
     let mut syntax_errors = 0usize;
     let mut crashes = 0usize;
     let mut timeouts = 0usize;
-    let mut best_score: u64 = 0;
+    let mut divergences = 0usize;
+    let mut optimizer_bugs = 0usize;
+    let mut conformance_bugs = 0usize;
+    let mut best_score: u64 = corpus.entries.iter().map(|e| e.score).max().unwrap_or(0);
     let score_re = if !args.score_regex.is_empty() {
         Some(Regex::new(&args.score_regex).expect("invalid --score-regex"))
     } else { None };
 
     for i in 0..args.iters {
-        // pick a random seed and extract template
-        let seed_path = seeds[rng.gen_range(0..seeds.len())].clone();
-        let seed_src = read_to_string(&seed_path)?;
+        // pick a seed via the energy schedule: favor high-score, rarely-chosen,
+        // recently-fruitful entries over a flat uniform draw
+        let chosen_idx = corpus.choose(&mut rng, i);
+        corpus.record_choice(chosen_idx);
+        let seed_src = corpus.entries[chosen_idx].src.clone();
         let tpl_a = extractor.extract(&seed_src);
 
         // occasionally fuse with another template
-        let tpl = if rng.gen_bool(0.2) {
-            let other = &seeds[rng.gen_range(0..seeds.len())];
-            let other_src = read_to_string(other)?;
+        let fused = rng.gen_bool(0.2);
+        let tpl = if fused {
+            let other_idx = rng.gen_range(0..corpus.entries.len());
+            let other_src = corpus.entries[other_idx].src.clone();
             let tpl_b = extractor.extract(&other_src);
             kre8ntemjs_core::Mutator::fuse(&tpl_a, &tpl_b)
         } else {
@@ -133,15 +209,58 @@ fn main() -> anyhow::Result<()> {
         };
 
         // concretize
-        let prog = Concretizer::concretize(&mutated, &mut rng);
+        let concretized = Concretizer::concretize(&mutated, &mut rng);
 
-        // run
-        let outcome = eng.run_js(&prog)?;
+        // Re-derive the frontmatter from the program actually about to run, not from
+        // `seed_src`: mutation/fusion routinely changes the executed code out from under
+        // the seed it started from, so `negative:` metadata describing the *original*
+        // seed can no longer be trusted to describe it. A fused program additionally mixes
+        // in an unrelated seed's code, so we never attempt conformance classification there.
+        let prog_meta = SeedMeta::parse(&concretized);
+
+        // apply the program's own frontmatter flags/includes (strict prologue, module wrapper,
+        // named harness snippets) before anything executes it
+        let prog = seed::wrap_program(&concretized, &prog_meta, args.harness_dir.as_deref())?;
+
+        // differential-testing mode: compare the primary engine against every --diff-engine-cmd
+        if let Some(set) = &diff_set {
+            if let Some(runs) = set.diff_run(&prog, normalize_output)? {
+                if EngineSet::diverges(&runs, normalize_output) {
+                    divergences += 1;
+                    write_diff_report(&args.out, i, &prog, &runs)?;
+                }
+            }
+        }
+
+        // optimizer-oracle: a faithful rewrite must behave identically to the original.
+        // Its run of `prog` doubles as the main run below, instead of paying for the
+        // same engine invocation twice.
+        let mut outcome: Option<RunOutcome> = None;
+        if args.optimizer_oracle {
+            if let Some(variant) = Transformer::optimize(&prog) {
+                let orig = eng.run_js(&prog)?;
+                let transformed = eng.run_js(&variant)?;
+                let orig_is_syntax_error = is_syntax_error(&orig.stderr);
+                if !orig_is_syntax_error {
+                    let mismatch = orig.status != transformed.status
+                        || normalize_output(&orig.stdout) != normalize_output(&transformed.stdout);
+                    if mismatch {
+                        optimizer_bugs += 1;
+                        write_optimizer_report(&args.out, i, &prog, &variant, &orig, &transformed)?;
+                    }
+                }
+                outcome = Some(orig);
+            }
+        }
+
+        // run (reusing the optimizer-oracle's run of `prog` above if we already have one)
+        let outcome = match outcome {
+            Some(o) => o,
+            None => eng.run_js(&prog)?,
+        };
 
         // Filter out plain syntax errors; keep real crashes/timeouts.
-        let is_syntax_error = outcome.stderr.contains("SyntaxError")
-            || outcome.stderr.contains("Parse error")
-            || outcome.stderr.contains("Unexpected token");
+        let is_syntax_error = is_syntax_error(&outcome.stderr);
 
         if is_syntax_error {
             syntax_errors += 1;
@@ -158,14 +277,39 @@ fn main() -> anyhow::Result<()> {
         // Decide whether this is "interesting enough" to save/promote
         let is_increasing = if let Some(s) = cov_score { s > best_score } else { false };
 
-        if outcome.timed_out {
+        // Coverage-guided corpus growth: keep (rather than discard) any mutated program
+        // that pushes past the global best or lands on a coverage value not seen before,
+        // crediting the parent seed so the energy schedule keeps favoring it.
+        if let Some(s) = cov_score {
+            let unseen = seen_scores.insert(s);
+            if s > best_score || unseen {
+                corpus.add(prog.clone(), s, i);
+                corpus.mark_new_coverage(chosen_idx, i, s);
+            }
+            if s > best_score {
+                best_score = s;
+            }
+        }
+
+        if !fused && prog_meta.negative.is_some() {
+            // Test262-style negative seed: the interesting case is the *expected*
+            // failure not happening, which is a spec-conformance bug rather than a crash.
+            let neg = prog_meta.negative.as_ref().unwrap();
+            if seed::classify(&prog_meta, &outcome) == SeedVerdict::ConformanceBug {
+                let sig = crash_signature(&outcome.stderr);
+                if seen_conformance.insert(format!("{}:{}", neg.r#type, sig)) {
+                    conformance_bugs += 1;
+                    let path = args.out.join(format!("conformance_iter{}_want_{}_sig{}.js", i, neg.r#type, &sig[..8]));
+                    std::fs::write(&path, &prog)?;
+                    let stderr_path = args.out.join(format!("conformance_iter{}_want_{}_sig{}.stderr.txt", i, neg.r#type, &sig[..8]));
+                    std::fs::write(&stderr_path, &outcome.stderr)?;
+                }
+            }
+        } else if outcome.timed_out {
             // Gate on increasing coverage if requested
             if args.keep_only_increasing && score_re.is_some() && !is_increasing {
                 // skip saving; not increasing coverage
             } else {
-                if let Some(s) = cov_score {
-                    if s > best_score { best_score = s; }
-                }
                 timeouts += 1;
                 let sig = crash_signature(&outcome.stderr);
                 if seen.insert(sig.clone()) {
@@ -182,9 +326,6 @@ fn main() -> anyhow::Result<()> {
                 if args.keep_only_increasing && score_re.is_some() && !is_increasing {
                     // skip saving; not increasing coverage
                 } else {
-                    if let Some(s) = cov_score {
-                        if s > best_score { best_score = s; }
-                    }
                     let sig = crash_signature(&outcome.stderr);
                     if seen.insert(sig.clone()) {
                         crashes += 1;
@@ -210,12 +351,15 @@ fn main() -> anyhow::Result<()> {
         }
 
         if i % 100 == 0 {
-            eprintln!("iter {i} | syntax={syntax_errors} unique_crashes={crashes} timeouts={timeouts}");
+            eprintln!("iter {i} | syntax={syntax_errors} unique_crashes={crashes} timeouts={timeouts} diverges={divergences} optbugs={optimizer_bugs} conformance={conformance_bugs} corpus={}", corpus.entries.len());
+            corpus.save(&corpus_path)?;
         }
     }
 
+    corpus.save(&corpus_path)?;
+
     eprintln!("\n=== summary ===");
-    eprintln!("syntax={syntax_errors} unique_crashes={crashes} timeouts={timeouts}");
+    eprintln!("syntax={syntax_errors} unique_crashes={crashes} timeouts={timeouts} diverges={divergences} optbugs={optimizer_bugs} conformance={conformance_bugs} corpus={}", corpus.entries.len());
 
     Ok(())
 }
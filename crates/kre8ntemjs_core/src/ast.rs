@@ -18,6 +18,27 @@ impl JsAst {
     }
 }
 
+/// Common statement-ish node types in the TS-JS grammar.
+pub fn is_statement_kind(kind: &str) -> bool {
+    matches!(kind,
+        "statement_block"
+        | "variable_declaration"
+        | "lexical_declaration"
+        | "expression_statement"
+        | "if_statement"
+        | "for_statement"
+        | "for_in_statement"
+        | "for_of_statement"
+        | "while_statement"
+        | "do_statement"
+        | "return_statement"
+        | "throw_statement"
+        | "try_statement"
+        | "function_declaration"
+        | "class_declaration"
+    )
+}
+
 /// Collect statement nodes (safe insertion boundaries).
 pub fn collect_statement_nodes<'a>(tree: &'a Tree, src: &str) -> Vec<Node<'a>> {
     let mut out = Vec::new();
@@ -26,25 +47,7 @@ pub fn collect_statement_nodes<'a>(tree: &'a Tree, src: &str) -> Vec<Node<'a>> {
     let mut stack = vec![root];
 
     while let Some(n) = stack.pop() {
-        // Common statement-ish node types in TS-JS grammar
-        let ty = n.kind();
-        if matches!(ty,
-            "statement_block"
-            | "variable_declaration"
-            | "lexical_declaration"
-            | "expression_statement"
-            | "if_statement"
-            | "for_statement"
-            | "for_in_statement"
-            | "for_of_statement"
-            | "while_statement"
-            | "do_statement"
-            | "return_statement"
-            | "throw_statement"
-            | "try_statement"
-            | "function_declaration"
-            | "class_declaration"
-        ) {
+        if is_statement_kind(n.kind()) {
             out.push(n);
         }
         if n.child_count() > 0 {
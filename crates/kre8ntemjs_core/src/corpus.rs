@@ -0,0 +1,168 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// One accepted input in the corpus, together with the state the energy schedule needs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorpusEntry {
+    pub src: String,
+    pub score: u64,
+    pub times_chosen: u64,
+    pub last_new_cov_iter: u64,
+}
+
+/// Persistent, coverage-guided seed queue. Replaces a flat `Vec<seed>` sampled
+/// uniformly: entries are biased by an AFL-style energy schedule so that inputs which
+/// recently discovered new coverage get picked (and mutated further) more often.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Corpus {
+    pub entries: Vec<CorpusEntry>,
+}
+
+/// How recently `last_new_cov_iter` has to be (in iterations) to earn the energy boost.
+const RECENCY_WINDOW: u64 = 200;
+const RECENCY_BOOST: f64 = 4.0;
+
+impl Corpus {
+    pub fn from_seeds(seeds: Vec<String>) -> Self {
+        Self {
+            entries: seeds
+                .into_iter()
+                .map(|src| CorpusEntry { src, score: 0, times_chosen: 0, last_new_cov_iter: 0 })
+                .collect(),
+        }
+    }
+
+    /// Sample an entry index with probability proportional to `score / (1 + times_chosen)`,
+    /// boosted for entries that found new coverage within `RECENCY_WINDOW` iterations.
+    pub fn choose<R: Rng>(&self, rng: &mut R, current_iter: u64) -> usize {
+        let weights: Vec<f64> = self
+            .entries
+            .iter()
+            .map(|e| {
+                let base = e.score as f64 / (1.0 + e.times_chosen as f64);
+                let recency = current_iter.saturating_sub(e.last_new_cov_iter);
+                if recency < RECENCY_WINDOW { base * RECENCY_BOOST } else { base }
+            })
+            .collect();
+
+        let total: f64 = weights.iter().sum();
+        if total <= 0.0 {
+            return rng.gen_range(0..self.entries.len());
+        }
+        let mut pick = rng.gen::<f64>() * total;
+        for (i, w) in weights.iter().enumerate() {
+            if pick < *w {
+                return i;
+            }
+            pick -= *w;
+        }
+        self.entries.len() - 1
+    }
+
+    pub fn record_choice(&mut self, idx: usize) {
+        self.entries[idx].times_chosen += 1;
+    }
+
+    /// Keep a mutated program that discovered new coverage instead of discarding it.
+    pub fn add(&mut self, src: String, score: u64, iter: u64) {
+        self.entries.push(CorpusEntry { src, score, times_chosen: 0, last_new_cov_iter: iter });
+    }
+
+    /// Credit the parent entry that led to a new-coverage find, so the schedule keeps
+    /// favoring it while it's fresh.
+    pub fn mark_new_coverage(&mut self, idx: usize, iter: u64, score: u64) {
+        let e = &mut self.entries[idx];
+        e.last_new_cov_iter = iter;
+        if score > e.score {
+            e.score = score;
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Option<Corpus>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let data = fs::read_to_string(path)?;
+        Ok(Some(serde_json::from_str(&data)?))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let data = serde_json::to_string_pretty(self)?;
+        fs::write(path, data)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn entry(score: u64, times_chosen: u64, last_new_cov_iter: u64) -> CorpusEntry {
+        CorpusEntry { src: String::new(), score, times_chosen, last_new_cov_iter }
+    }
+
+    #[test]
+    fn prefers_high_score_low_chosen_entries() {
+        let corpus = Corpus {
+            entries: vec![entry(100, 0, 0), entry(1, 50, 0)],
+        };
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut picks = [0u32; 2];
+        for _ in 0..1000 {
+            picks[corpus.choose(&mut rng, 0)] += 1;
+        }
+        assert!(picks[0] > picks[1] * 10);
+    }
+
+    #[test]
+    fn boosts_entries_that_recently_found_new_coverage() {
+        let corpus = Corpus {
+            entries: vec![entry(10, 0, 0), entry(10, 0, 500)],
+        };
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut picks = [0u32; 2];
+        for _ in 0..1000 {
+            picks[corpus.choose(&mut rng, 500)] += 1;
+        }
+        assert!(picks[1] > picks[0]);
+    }
+
+    #[test]
+    fn add_and_mark_new_coverage_grow_and_credit_the_corpus() {
+        let mut corpus = Corpus::from_seeds(vec!["seed".into()]);
+        corpus.record_choice(0);
+        assert_eq!(corpus.entries[0].times_chosen, 1);
+
+        corpus.add("mutant".into(), 5, 3);
+        assert_eq!(corpus.entries.len(), 2);
+        assert_eq!(corpus.entries[1].score, 5);
+
+        corpus.mark_new_coverage(0, 10, 20);
+        assert_eq!(corpus.entries[0].score, 20);
+        assert_eq!(corpus.entries[0].last_new_cov_iter, 10);
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let dir = std::env::temp_dir().join(format!("kre8ntemjs_corpus_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("corpus.json");
+        let corpus = Corpus::from_seeds(vec!["x".into(), "y".into()]);
+        corpus.save(&path).unwrap();
+        let loaded = Corpus::load(&path).unwrap().expect("should load");
+        assert_eq!(loaded.entries.len(), 2);
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn load_returns_none_for_a_missing_path() {
+        let path = std::env::temp_dir().join("kre8ntemjs_corpus_test_missing.json");
+        assert!(Corpus::load(&path).unwrap().is_none());
+    }
+}
@@ -14,8 +14,13 @@ use thiserror::Error;
 pub mod ast;
 pub mod minimizer;
 pub mod dataflow;
+pub mod transform;
+pub mod seed;
+pub mod corpus;
 
 pub use minimizer::minimize_preserving_coverage;
+pub use transform::Transformer;
+pub use corpus::Corpus;
 
 #[derive(Debug, Error)]
 pub enum FuzzError {
@@ -262,6 +267,16 @@ pub struct RunOutcome {
     pub stdout: String,
 }
 
+/// True if `stderr` looks like a plain grammar/parse failure rather than a real
+/// crash or bug. Different engines phrase this differently, so every caller that
+/// needs to tell "this program is just invalid JS" apart from "this is a bug" must
+/// check all of these, not just `"SyntaxError"`.
+pub fn is_syntax_error(stderr: &str) -> bool {
+    stderr.contains("SyntaxError")
+        || stderr.contains("Parse error")
+        || stderr.contains("Unexpected token")
+}
+
 impl Engine {
     pub fn run_js(&self, js: &str) -> Result<RunOutcome> {
         let mut tmp = NamedTempFile::new()?;
@@ -352,6 +367,64 @@ impl Engine {
     }
 }
 
+/// A set of interchangeable JS engines for differential testing (e.g. d8, jsc,
+/// spidermonkey). The same program is run on every engine in the set so that
+/// genuine divergences can be told apart from grammar or engine-specific quirks.
+#[derive(Debug, Clone)]
+pub struct EngineSet {
+    pub engines: Vec<Engine>,
+}
+
+/// One engine's outcome for a differential run, tagged with the command that produced it.
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffRun {
+    pub cmd: String,
+    pub outcome: RunOutcome,
+}
+
+impl EngineSet {
+    pub fn new(engines: Vec<Engine>) -> Self {
+        Self { engines }
+    }
+
+    /// Run `js` on every engine, skipping the set entirely if any engine reports a
+    /// syntax error (grammar differences, not bugs) or if a single engine's own output is
+    /// unstable across two consecutive runs (nondeterministic programs can't be diffed).
+    /// Returns `None` when the program isn't safe to compare across engines.
+    pub fn diff_run(&self, js: &str, normalize: impl Fn(&str) -> String) -> Result<Option<Vec<DiffRun>>> {
+        let mut runs = Vec::with_capacity(self.engines.len());
+        for e in &self.engines {
+            let first = e.run_js(js)?;
+            if is_syntax_error(&first.stderr) {
+                return Ok(None);
+            }
+            let second = e.run_js(js)?;
+            if normalize(&first.stdout) != normalize(&second.stdout) {
+                return Ok(None);
+            }
+            runs.push(DiffRun { cmd: e.cmd.clone(), outcome: first });
+        }
+        Ok(Some(runs))
+    }
+
+    /// True if two or more engines that both exited cleanly produced different normalized stdout.
+    pub fn diverges(runs: &[DiffRun], normalize: impl Fn(&str) -> String) -> bool {
+        let mut seen: Option<String> = None;
+        for r in runs {
+            if r.outcome.status != 0 {
+                continue;
+            }
+            let n = normalize(&r.outcome.stdout);
+            match &seen {
+                None => seen = Some(n),
+                Some(prev) if *prev != n => return true,
+                _ => {}
+            }
+        }
+        false
+    }
+}
+
 /// Corpus utilities
 pub fn load_seed_paths(seeds_dir: &Path) -> Result<Vec<PathBuf>> {
     let mut files = Vec::new();
@@ -370,3 +443,61 @@ pub fn read_to_string(p: &Path) -> Result<String> {
     Ok(fs::read_to_string(p)?)
 }
 
+/// Sum every captured number `re` finds in `out`/`err`, underscores stripped (e.g. so a
+/// `covered: (\d[\d_]*)` regex can pick up `covered: 12_345`). Shared by the coverage
+/// scoring pass and the minimizer's coverage-preserving reduction, so both agree on how
+/// a regex-scraped score is computed.
+pub fn score_with_regex(out: &str, err: &str, re: &Regex) -> u64 {
+    let mut sum = 0u64;
+    for cap in re.captures_iter(out) {
+        for i in 1..cap.len() {
+            if let Ok(v) = cap[i].replace('_', "").parse::<u64>() { sum += v; }
+        }
+    }
+    for cap in re.captures_iter(err) {
+        for i in 1..cap.len() {
+            if let Ok(v) = cap[i].replace('_', "").parse::<u64>() { sum += v; }
+        }
+    }
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(cmd: &str, status: i32, stdout: &str) -> DiffRun {
+        DiffRun {
+            cmd: cmd.to_string(),
+            outcome: RunOutcome { status, timed_out: false, stdout: stdout.to_string(), stderr: String::new() },
+        }
+    }
+
+    #[test]
+    fn diverges_when_clean_engines_disagree() {
+        let runs = vec![run("a", 0, "1"), run("b", 0, "2")];
+        assert!(EngineSet::diverges(&runs, |s| s.to_string()));
+    }
+
+    #[test]
+    fn does_not_diverge_when_clean_engines_agree() {
+        let runs = vec![run("a", 0, "1"), run("b", 0, "1")];
+        assert!(!EngineSet::diverges(&runs, |s| s.to_string()));
+    }
+
+    #[test]
+    fn ignores_a_nonzero_exit_engine_when_checking_divergence() {
+        // `b` crashed, so its stdout shouldn't be compared against `a`'s at all.
+        let runs = vec![run("a", 0, "1"), run("b", 1, "garbage")];
+        assert!(!EngineSet::diverges(&runs, |s| s.to_string()));
+    }
+
+    #[test]
+    fn is_syntax_error_recognizes_all_three_phrasings() {
+        assert!(is_syntax_error("Uncaught SyntaxError: oops"));
+        assert!(is_syntax_error("Parse error at line 1"));
+        assert!(is_syntax_error("Unexpected token }"));
+        assert!(!is_syntax_error("RangeError: stack overflow"));
+    }
+}
+
@@ -1,32 +1,126 @@
-use crate::Engine;
+use crate::ast::{collect_statement_nodes, JsAst};
+use crate::{score_with_regex, Engine};
 use anyhow::Result;
 
-/// Greedy line-drop reducer: removes lines if the crash signature (caller-provided)
-/// remains the same. Keep it tiny and fast—good enough for triage.
-pub fn minimize_by<F>(prog: &str, engine: &Engine, same_bug: F) -> Result<String>
+/// A reducible unit: the byte range of one top-level statement node.
+#[derive(Debug, Clone, Copy)]
+struct Unit {
+    start: usize,
+    end: usize,
+}
+
+/// Top-level statement units of `src`, in source order. `None` if `src` doesn't parse
+/// or has no recognizable statements, in which case the caller should skip reduction
+/// rather than fall back to line-based mangling.
+fn statement_units(src: &str) -> Option<Vec<Unit>> {
+    let mut js = JsAst::default();
+    let tree = js.parse(src)?;
+    if tree.root_node().has_error() {
+        return None;
+    }
+    let root = tree.root_node();
+    let mut units: Vec<Unit> = collect_statement_nodes(&tree, src)
+        .into_iter()
+        .filter(|n| n.parent().map(|p| p.id()) == Some(root.id()))
+        .map(|n| Unit { start: n.start_byte(), end: n.end_byte() })
+        .collect();
+    units.sort_by_key(|u| u.start);
+    units.dedup_by_key(|u| u.start);
+    if units.is_empty() { None } else { Some(units) }
+}
+
+/// Concatenate the kept units' source text and make sure the result still parses, so we
+/// never waste an engine run on a candidate that's merely garbled tree-sitter input.
+fn reconstruct(src: &str, units: &[Unit]) -> Option<String> {
+    let mut out = String::with_capacity(src.len());
+    for u in units {
+        out.push_str(&src[u.start..u.end]);
+        out.push('\n');
+    }
+    let mut js = JsAst::default();
+    js.parse(&out).filter(|t| !t.root_node().has_error()).map(|_| out)
+}
+
+/// ddmin (Zeller & Hildebrandt): shrink `units` to a 1-minimal subset that still
+/// satisfies `still_interesting`, trying only candidates that reparse.
+fn ddmin<F>(units: Vec<Unit>, src: &str, still_interesting: &F) -> Vec<Unit>
 where
     F: Fn(&str) -> bool,
 {
-    let mut lines: Vec<&str> = prog.lines().collect();
-    let mut i = 0usize;
-    while i < lines.len() && lines.len() > 1 {
-        let candidate = {
-            let mut c = lines.clone();
-            c.remove(i);
-            c.join("\n")
-        };
-        let out = engine.run_js(&candidate)?;
-        if same_bug(&out.stderr) {
-            lines.remove(i); // accept the deletion
-            // do not advance; try the same index again
-        } else {
-            i += 1;
+    let mut kept = units;
+    let mut n = 2usize;
+
+    while kept.len() >= 2 {
+        let chunk_size = (kept.len() + n - 1) / n;
+        let chunks: Vec<&[Unit]> = kept.chunks(chunk_size).collect();
+        let mut reduced = false;
+
+        // Does any single chunk alone still reproduce the bug?
+        for chunk in &chunks {
+            if let Some(candidate) = reconstruct(src, chunk) {
+                if still_interesting(&candidate) {
+                    kept = chunk.to_vec();
+                    n = 2;
+                    reduced = true;
+                    break;
+                }
+            }
+        }
+
+        // Else, does removing one whole chunk (keeping its complement) still work?
+        if !reduced {
+            for chunk in &chunks {
+                let complement: Vec<Unit> = kept
+                    .iter()
+                    .copied()
+                    .filter(|u| !chunk.iter().any(|c| c.start == u.start && c.end == u.end))
+                    .collect();
+                if let Some(candidate) = reconstruct(src, &complement) {
+                    if still_interesting(&candidate) {
+                        kept = complement;
+                        n = (n - 1).max(2);
+                        reduced = true;
+                        break;
+                    }
+                }
+            }
+        }
+
+        // Neither helped at this granularity: go finer, or stop if already at max granularity.
+        if !reduced {
+            if n >= kept.len() {
+                break;
+            }
+            n = (2 * n).min(kept.len());
         }
     }
-    Ok(lines.join("\n"))
+
+    kept
 }
 
-/// Coverage-preserving minimizer: keep candidate if scorer(candidate) >= target.
+/// Statement-aware ddmin reducer: shrinks `prog` to a 1-minimal subset of top-level
+/// statements (via `collect_statement_nodes`) that still reproduces the same crash,
+/// per `same_bug(stderr)`. Every candidate is reparsed before it's run on the engine,
+/// so a dropped statement never leaves behind a program the grammar can't accept.
+pub fn minimize_by<F>(prog: &str, engine: &Engine, same_bug: F) -> Result<String>
+where
+    F: Fn(&str) -> bool,
+{
+    let units = match statement_units(prog) {
+        Some(u) => u,
+        None => return Ok(prog.to_string()),
+    };
+    let test = |candidate: &str| -> bool {
+        match engine.run_js(candidate) {
+            Ok(out) => same_bug(&out.stderr),
+            Err(_) => false,
+        }
+    };
+    let kept = ddmin(units, prog, &test);
+    Ok(reconstruct(prog, &kept).unwrap_or_else(|| prog.to_string()))
+}
+
+/// As `minimize_by`, but keeps a candidate if `scorer(candidate) >= target_score`.
 pub fn minimize_preserving_coverage(
     prog: &str,
     engine: &Engine,
@@ -34,34 +128,62 @@ pub fn minimize_preserving_coverage(
     scorer: &regex::Regex,
     target_score: u64,
 ) -> Result<String> {
-    let mut lines: Vec<&str> = prog.lines().collect();
-    let mut i = 0usize;
-    while i < lines.len() && lines.len() > 1 {
-        let candidate = {
-            let mut c = lines.clone();
-            c.remove(i);
-            c.join("\n")
-        };
-        let out = engine.run_js_with_args(&candidate, score_args)?;
-        let s = {
-            let mut sum = 0u64;
-            for cap in scorer.captures_iter(&out.stdout) {
-                for j in 1..cap.len() {
-                    if let Ok(v) = cap[j].replace('_', "").parse::<u64>() { sum += v; }
-                }
-            }
-            for cap in scorer.captures_iter(&out.stderr) {
-                for j in 1..cap.len() {
-                    if let Ok(v) = cap[j].replace('_', "").parse::<u64>() { sum += v; }
-                }
-            }
-            sum
-        };
-        if s >= target_score {
-            lines.remove(i); // accept deletion
-        } else {
-            i += 1;
+    let units = match statement_units(prog) {
+        Some(u) => u,
+        None => return Ok(prog.to_string()),
+    };
+    let test = |candidate: &str| -> bool {
+        match engine.run_js_with_args(candidate, score_args) {
+            Ok(out) => score_with_regex(&out.stdout, &out.stderr, scorer) >= target_score,
+            Err(_) => false,
         }
+    };
+    let kept = ddmin(units, prog, &test);
+    Ok(reconstruct(prog, &kept).unwrap_or_else(|| prog.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statement_units_finds_top_level_statements() {
+        let src = "let a = 1;\nif (a) { let b = 2; }\nfunction f() {}\n";
+        let units = statement_units(src).expect("should parse");
+        assert_eq!(units.len(), 3);
+    }
+
+    #[test]
+    fn statement_units_none_for_empty_source() {
+        assert!(statement_units("").is_none());
+    }
+
+    #[test]
+    fn reconstruct_concatenates_kept_units_in_order() {
+        let src = "let a = 1;\nlet b = 2;\nlet c = 3;\n";
+        let units = statement_units(src).unwrap();
+        let out = reconstruct(src, &[units[0], units[2]]).expect("should reparse");
+        assert_eq!(out, "let a = 1;\nlet c = 3;\n");
+    }
+
+    #[test]
+    fn ddmin_shrinks_to_the_single_unit_that_reproduces_the_bug() {
+        let src = "let a = 1;\nlet bug = 2;\nlet c = 3;\n";
+        let units = statement_units(src).unwrap();
+        assert_eq!(units.len(), 3);
+        let kept = ddmin(units, src, &|candidate: &str| candidate.contains("bug"));
+        assert_eq!(kept.len(), 1);
+        let result = reconstruct(src, &kept).unwrap();
+        assert!(result.contains("bug"));
+    }
+
+    #[test]
+    fn ddmin_keeps_everything_when_the_whole_program_is_needed() {
+        let src = "let a = 1;\nlet b = 2;\n";
+        let units = statement_units(src).unwrap();
+        let kept = ddmin(units, src, &|candidate: &str| {
+            candidate.contains('a') && candidate.contains('b')
+        });
+        assert_eq!(kept.len(), 2);
     }
-    Ok(lines.join("\n"))
 }
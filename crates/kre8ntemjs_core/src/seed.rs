@@ -0,0 +1,257 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::RunOutcome;
+
+/// Test262-style `negative` frontmatter: the kind of failure the seed is *expected* to
+/// produce (e.g. `{ phase: parse, type: SyntaxError }`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NegativeMeta {
+    pub phase: String,
+    pub r#type: String,
+}
+
+/// Metadata parsed from a seed's leading `/*--- ... ---*/` Test262-style frontmatter.
+/// A seed with no frontmatter gets the default (no `negative`, no `flags`, no
+/// `includes`) and is treated exactly as it was before this existed.
+#[derive(Debug, Clone, Default)]
+pub struct SeedMeta {
+    pub negative: Option<NegativeMeta>,
+    pub flags: Vec<String>,
+    pub includes: Vec<String>,
+}
+
+/// How a run compared against the seed's declared expectations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeedVerdict {
+    /// Positive test that ran clean, or negative test that failed the expected way.
+    Expected,
+    /// A `negative` seed didn't fail the way it declared it would: a conformance bug.
+    ConformanceBug,
+    /// A positive seed threw unexpectedly: a candidate crash.
+    Candidate,
+}
+
+impl SeedMeta {
+    /// Parse the leading `/*--- ... ---*/` frontmatter block, if any.
+    pub fn parse(src: &str) -> SeedMeta {
+        let mut meta = SeedMeta::default();
+        let block = match frontmatter_block(src) {
+            Some(b) => b,
+            None => return meta,
+        };
+
+        let mut lines = block.lines().peekable();
+        while let Some(line) = lines.next() {
+            let trimmed = line.trim();
+            if let Some(rest) = trimmed.strip_prefix("negative:") {
+                meta.negative = parse_negative(rest.trim(), &mut lines);
+            } else if let Some(rest) = trimmed.strip_prefix("flags:") {
+                meta.flags = parse_flow_list(rest.trim());
+            } else if let Some(rest) = trimmed.strip_prefix("includes:") {
+                meta.includes = parse_flow_list(rest.trim());
+            }
+        }
+        meta
+    }
+}
+
+fn frontmatter_block(src: &str) -> Option<&str> {
+    let start = src.find("/*---")?;
+    let after = &src[start + "/*---".len()..];
+    let end = after.find("---*/")?;
+    Some(&after[..end])
+}
+
+/// `negative:` can be a flow mapping (`{ phase: parse, type: SyntaxError }`) or a block
+/// mapping spread over the following indented lines; handle both.
+fn parse_negative<'a>(
+    inline: &str,
+    lines: &mut std::iter::Peekable<std::str::Lines<'a>>,
+) -> Option<NegativeMeta> {
+    let mut phase = String::new();
+    let mut ty = String::new();
+
+    if let Some(obj) = inline.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+        for part in obj.split(',') {
+            if let Some((k, v)) = part.split_once(':') {
+                match k.trim() {
+                    "phase" => phase = v.trim().to_string(),
+                    "type" => ty = v.trim().to_string(),
+                    _ => {}
+                }
+            }
+        }
+    } else {
+        while let Some(next) = lines.peek() {
+            if !next.starts_with(' ') && !next.starts_with('\t') {
+                break;
+            }
+            let nt = next.trim();
+            if let Some(v) = nt.strip_prefix("phase:") {
+                phase = v.trim().to_string();
+            } else if let Some(v) = nt.strip_prefix("type:") {
+                ty = v.trim().to_string();
+            }
+            lines.next();
+        }
+    }
+
+    if phase.is_empty() && ty.is_empty() {
+        None
+    } else {
+        Some(NegativeMeta { phase, r#type: ty })
+    }
+}
+
+fn parse_flow_list(s: &str) -> Vec<String> {
+    s.trim_start_matches('[')
+        .trim_end_matches(']')
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Wrap `src` per the seed's `flags`, prepending its `includes` from `harness_dir`.
+/// `raw` seeds are returned untouched, matching Test262 semantics (no harness, no
+/// implicit strict mode, no wrapping).
+pub fn wrap_program(src: &str, meta: &SeedMeta, harness_dir: Option<&Path>) -> Result<String> {
+    if meta.flags.iter().any(|f| f == "raw") {
+        return Ok(src.to_string());
+    }
+
+    let mut out = String::new();
+    // The directive must come first, ahead of any harness code, to be a prologue.
+    if meta.flags.iter().any(|f| f == "onlyStrict") {
+        out.push_str("\"use strict\";\n");
+    }
+
+    if let Some(dir) = harness_dir {
+        for inc in &meta.includes {
+            let path = dir.join(inc);
+            let snippet = fs::read_to_string(&path)
+                .with_context(|| format!("failed to read harness include {}", path.display()))?;
+            out.push_str(&snippet);
+            if !snippet.ends_with('\n') {
+                out.push('\n');
+            }
+        }
+    }
+
+    if meta.flags.iter().any(|f| f == "module") {
+        out.push_str(&format!("(function(){{\n{}\n}})();\n", src));
+    } else {
+        out.push_str(src);
+    }
+
+    Ok(out)
+}
+
+/// Classify a run against what the seed declared it should do.
+pub fn classify(meta: &SeedMeta, outcome: &RunOutcome) -> SeedVerdict {
+    match &meta.negative {
+        Some(neg) => {
+            let failed_as_declared = outcome.status != 0 && outcome.stderr.contains(&neg.r#type);
+            if failed_as_declared {
+                SeedVerdict::Expected
+            } else {
+                SeedVerdict::ConformanceBug
+            }
+        }
+        None => {
+            if outcome.status != 0 {
+                SeedVerdict::Candidate
+            } else {
+                SeedVerdict::Expected
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome(status: i32, stderr: &str) -> RunOutcome {
+        RunOutcome { status, timed_out: false, stdout: String::new(), stderr: stderr.to_string() }
+    }
+
+    #[test]
+    fn parses_flow_style_negative_and_lists() {
+        let src = "/*---\nnegative: { phase: parse, type: SyntaxError }\nflags: [onlyStrict, raw]\nincludes: [a.js, b.js]\n---*/\nvar x;\n";
+        let meta = SeedMeta::parse(src);
+        let neg = meta.negative.expect("negative block should parse");
+        assert_eq!(neg.phase, "parse");
+        assert_eq!(neg.r#type, "SyntaxError");
+        assert_eq!(meta.flags, vec!["onlyStrict", "raw"]);
+        assert_eq!(meta.includes, vec!["a.js", "b.js"]);
+    }
+
+    #[test]
+    fn parses_block_style_negative() {
+        let src = "/*---\nnegative:\n  phase: resolution\n  type: ReferenceError\n---*/\nx;\n";
+        let meta = SeedMeta::parse(src);
+        let neg = meta.negative.expect("negative block should parse");
+        assert_eq!(neg.phase, "resolution");
+        assert_eq!(neg.r#type, "ReferenceError");
+    }
+
+    #[test]
+    fn defaults_to_no_metadata_without_frontmatter() {
+        let meta = SeedMeta::parse("let a = 1;\n");
+        assert!(meta.negative.is_none());
+        assert!(meta.flags.is_empty());
+        assert!(meta.includes.is_empty());
+    }
+
+    #[test]
+    fn classify_negative_seed_against_matching_and_mismatching_runs() {
+        let meta = SeedMeta {
+            negative: Some(NegativeMeta { phase: "parse".into(), r#type: "SyntaxError".into() }),
+            flags: vec![],
+            includes: vec![],
+        };
+        assert_eq!(
+            classify(&meta, &outcome(1, "Uncaught SyntaxError: oops")),
+            SeedVerdict::Expected
+        );
+        assert_eq!(classify(&meta, &outcome(0, "")), SeedVerdict::ConformanceBug);
+        assert_eq!(
+            classify(&meta, &outcome(1, "Uncaught TypeError: oops")),
+            SeedVerdict::ConformanceBug
+        );
+    }
+
+    #[test]
+    fn classify_positive_seed_against_clean_and_failing_runs() {
+        let meta = SeedMeta::default();
+        assert_eq!(classify(&meta, &outcome(0, "")), SeedVerdict::Expected);
+        assert_eq!(classify(&meta, &outcome(1, "boom")), SeedVerdict::Candidate);
+    }
+
+    #[test]
+    fn raw_flag_skips_wrapping_entirely() {
+        let meta = SeedMeta { negative: None, flags: vec!["raw".into()], includes: vec!["harness.js".into()] };
+        let wrapped = wrap_program("1;", &meta, None).unwrap();
+        assert_eq!(wrapped, "1;");
+    }
+
+    #[test]
+    fn only_strict_adds_use_strict_prologue() {
+        let meta = SeedMeta { negative: None, flags: vec!["onlyStrict".into()], includes: vec![] };
+        let wrapped = wrap_program("1;", &meta, None).unwrap();
+        assert!(wrapped.starts_with("\"use strict\";"));
+        assert!(wrapped.contains("1;"));
+    }
+
+    #[test]
+    fn module_flag_wraps_in_an_iife() {
+        let meta = SeedMeta { negative: None, flags: vec!["module".into()], includes: vec![] };
+        let wrapped = wrap_program("1;", &meta, None).unwrap();
+        assert!(wrapped.contains("(function(){"));
+        assert!(wrapped.contains("1;"));
+    }
+}
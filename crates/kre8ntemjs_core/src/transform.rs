@@ -0,0 +1,303 @@
+use crate::ast::{is_statement_kind, JsAst};
+use tree_sitter::Node;
+
+/// Semantics-preserving AST rewrites used by the optimizer-oracle: a faithful
+/// transform of a program must behave identically to the original, so any observed
+/// difference in behavior between the two is a miscompilation-class engine bug
+/// rather than an artifact of the rewrite itself.
+pub struct Transformer;
+
+struct Edit {
+    start: usize,
+    end: usize,
+    replacement: String,
+}
+
+impl Transformer {
+    /// Apply the internal rewrite set (constant folding, dead-literal-statement
+    /// elimination, single-use const inlining) to `src`. Returns `None` if no rewrite
+    /// applies, if any two rewrites overlap, if the result fails to reparse, or if the
+    /// top-level statement count changed by more than the rewrites can account for —
+    /// in all of those cases it's safer to skip the oracle check than risk comparing
+    /// against an invalid variant.
+    pub fn optimize(src: &str) -> Option<String> {
+        let mut js = JsAst::default();
+        let tree = js.parse(src)?;
+        let root = tree.root_node();
+        let before_stmts = top_level_statement_count(root);
+
+        let mut edits: Vec<Edit> = Vec::new();
+        let mut removed_stmts = 0usize;
+        collect_fold_edits(root, src, &mut edits);
+        collect_dce_edits(root, &mut edits, &mut removed_stmts);
+        collect_inline_edits(root, src, &mut edits, &mut removed_stmts);
+
+        if edits.is_empty() {
+            return None;
+        }
+
+        edits.sort_by_key(|e| e.start);
+        for w in edits.windows(2) {
+            if w[1].start < w[0].end {
+                return None;
+            }
+        }
+
+        let mut out = src.to_string();
+        for e in edits.iter().rev() {
+            out.replace_range(e.start..e.end, &e.replacement);
+        }
+
+        let mut check = JsAst::default();
+        let new_tree = check.parse(&out)?;
+        if new_tree.root_node().has_error() {
+            return None;
+        }
+        let after_stmts = top_level_statement_count(new_tree.root_node());
+        if before_stmts.saturating_sub(removed_stmts) != after_stmts {
+            return None;
+        }
+
+        Some(out)
+    }
+}
+
+fn top_level_statement_count(root: Node) -> usize {
+    let mut count = 0;
+    for i in 0..root.child_count() {
+        if let Some(c) = root.child(i) {
+            if is_statement_kind(c.kind()) {
+                count += 1;
+            }
+        }
+    }
+    count
+}
+
+/// Fold `<number> <op> <number>` into a single literal (+ - * /, integer-exact only).
+fn collect_fold_edits(root: Node, src: &str, edits: &mut Vec<Edit>) {
+    let mut stack = vec![root];
+    while let Some(n) = stack.pop() {
+        if n.kind() == "binary_expression" {
+            if let (Some(lhs), Some(rhs)) = (n.child_by_field_name("left"), n.child_by_field_name("right")) {
+                if lhs.kind() == "number" && rhs.kind() == "number" {
+                    let op = src[lhs.end_byte()..rhs.start_byte()].trim();
+                    let a = src[lhs.byte_range()].parse::<i64>().ok();
+                    let b = src[rhs.byte_range()].parse::<i64>().ok();
+                    if let (Some(a), Some(b)) = (a, b) {
+                        let folded = match op {
+                            "+" => a.checked_add(b),
+                            "-" => a.checked_sub(b),
+                            "*" => a.checked_mul(b),
+                            "/" if b != 0 && a % b == 0 => a.checked_div(b),
+                            _ => None,
+                        };
+                        if let Some(v) = folded {
+                            edits.push(Edit { start: n.start_byte(), end: n.end_byte(), replacement: v.to_string() });
+                        }
+                    }
+                }
+            }
+        }
+        for i in 0..n.child_count() {
+            if let Some(c) = n.child(i) { stack.push(c); }
+        }
+    }
+}
+
+/// Drop top-level expression statements that are a bare literal with no side effects.
+/// String statements in the leading directive prologue (`"use strict";`) are kept.
+fn collect_dce_edits(root: Node, edits: &mut Vec<Edit>, removed_stmts: &mut usize) {
+    let mut in_prologue = true;
+    for i in 0..root.child_count() {
+        let n = match root.child(i) { Some(n) => n, None => continue };
+        if n.kind() == "comment" {
+            continue;
+        }
+        let expr_kind = if n.kind() == "expression_statement" { n.child(0).map(|e| e.kind()) } else { None };
+        if in_prologue && expr_kind == Some("string") {
+            continue;
+        }
+        in_prologue = false;
+        if matches!(expr_kind, Some("number" | "string" | "true" | "false")) {
+            edits.push(Edit { start: n.start_byte(), end: n.end_byte(), replacement: String::new() });
+            *removed_stmts += 1;
+        }
+    }
+}
+
+/// Inline a top-level `const NAME = <literal-only initializer>;` at its single use site.
+/// The initializer must contain no identifiers at all, so it can never reference a
+/// mutable outer variable whose value could change between the declaration and the
+/// use. `NAME` also must not be shadowed anywhere else in the program (by a parameter,
+/// destructuring pattern, catch clause, etc.), since a shadow means some of the "uses"
+/// a flat name search finds don't actually refer to this declaration at all.
+fn collect_inline_edits(root: Node, src: &str, edits: &mut Vec<Edit>, removed_stmts: &mut usize) {
+    for i in 0..root.child_count() {
+        let decl = match root.child(i) {
+            Some(d) if d.kind() == "lexical_declaration" => d,
+            _ => continue,
+        };
+        if !decl.utf8_text(src.as_bytes()).unwrap_or("").trim_start().starts_with("const ") {
+            continue;
+        }
+        let declarators: Vec<Node> = (0..decl.named_child_count())
+            .filter_map(|j| decl.named_child(j))
+            .filter(|c| c.kind() == "variable_declarator")
+            .collect();
+        let declarator = match declarators.as_slice() { [d] => *d, _ => continue };
+        let name_node = match declarator.child_by_field_name("name") {
+            Some(n) if n.kind() == "identifier" => n,
+            _ => continue,
+        };
+        let value_node = match declarator.child_by_field_name("value") {
+            Some(v) => v,
+            None => continue,
+        };
+        if contains_identifier(value_node) {
+            continue;
+        }
+        let name = name_node.utf8_text(src.as_bytes()).unwrap_or("");
+        if name.is_empty() {
+            continue;
+        }
+        if has_shadowing_binding(root, src, name, name_node.id()) {
+            continue;
+        }
+        let uses = find_identifier_uses(root, src, name, name_node.id());
+        if uses.len() != 1 {
+            continue;
+        }
+        let value_text = value_node.utf8_text(src.as_bytes()).unwrap_or("").to_string();
+        edits.push(Edit { start: decl.start_byte(), end: decl.end_byte(), replacement: String::new() });
+        edits.push(Edit { start: uses[0].start_byte(), end: uses[0].end_byte(), replacement: value_text });
+        *removed_stmts += 1;
+    }
+}
+
+fn contains_identifier(node: Node) -> bool {
+    let mut stack = vec![node];
+    while let Some(n) = stack.pop() {
+        if n.kind() == "identifier" {
+            return true;
+        }
+        for i in 0..n.child_count() {
+            if let Some(c) = n.child(i) { stack.push(c); }
+        }
+    }
+    false
+}
+
+/// True if `name` is bound anywhere else in the program other than the declaration
+/// we're about to inline — as a plain parameter/declarator *or* as a destructuring
+/// pattern (`{name}`, `{...name}`, `[name]`, a catch parameter, a default-valued
+/// parameter, etc.). A flat identifier-name search can't tell such a binding from a
+/// use of the outer one, so any shadowing binding has to block inlining entirely
+/// rather than risk rewriting a reference that actually resolves to the shadow.
+fn has_shadowing_binding(root: Node, src: &str, name: &str, exclude_id: usize) -> bool {
+    let mut stack = vec![root];
+    while let Some(n) = stack.pop() {
+        if n.id() != exclude_id
+            && is_binding_occurrence(n)
+            && n.utf8_text(src.as_bytes()).unwrap_or("") == name
+        {
+            return true;
+        }
+        for i in 0..n.child_count() {
+            if let Some(c) = n.child(i) { stack.push(c); }
+        }
+    }
+    false
+}
+
+/// True if `n` is the node that introduces a binding (a name coming into scope),
+/// rather than a reference to one already in scope.
+fn is_binding_occurrence(n: Node) -> bool {
+    match n.kind() {
+        // `{n}` / `{n = 1}` inside an object destructuring pattern.
+        "shorthand_property_identifier_pattern" => true,
+        "identifier" => n
+            .parent()
+            .map(|p| {
+                matches!(
+                    p.kind(),
+                    // `function f(n) {}` / `function f([n]) {}`
+                    "formal_parameters"
+                        | "array_pattern"
+                        // `{...n}` rest element
+                        | "rest_pattern"
+                        // `catch (n) {}`
+                        | "catch_clause"
+                        // `let n = ...`
+                        | "variable_declarator"
+                        // `for (const n of xs)` / `for (let n in o)`
+                        | "for_in_statement"
+                        // `function f(n = 1) {}`, default-valued destructuring
+                        | "assignment_pattern"
+                )
+            })
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+fn find_identifier_uses<'a>(root: Node<'a>, src: &str, name: &str, exclude_id: usize) -> Vec<Node<'a>> {
+    let mut uses = Vec::new();
+    let mut stack = vec![root];
+    while let Some(n) = stack.pop() {
+        if n.kind() == "identifier" && n.id() != exclude_id && n.utf8_text(src.as_bytes()).unwrap_or("") == name {
+            uses.push(n);
+        }
+        for i in 0..n.child_count() {
+            if let Some(c) = n.child(i) { stack.push(c); }
+        }
+    }
+    uses
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folds_numeric_binary_expressions() {
+        let out = Transformer::optimize("let x = 1 + 2;\n").expect("fold should apply");
+        assert!(out.contains('3'));
+        assert!(!out.contains("1 + 2"));
+    }
+
+    #[test]
+    fn drops_bare_literal_statements() {
+        let out = Transformer::optimize("5;\nlet x = 1;\n").expect("dce should apply");
+        assert!(!out.contains("5;"));
+        assert!(out.contains("let x = 1;"));
+    }
+
+    #[test]
+    fn inlines_single_use_const_with_literal_initializer() {
+        let out = Transformer::optimize("const n = 42;\nlet y = n;\n").expect("inline should apply");
+        assert!(!out.contains("const n"));
+        assert!(out.contains("42"));
+    }
+
+    #[test]
+    fn never_inlines_a_binding_that_references_an_outer_variable() {
+        // `n`'s initializer is the mutable outer `m`, so it must not be inlined, and
+        // nothing else in this program is foldable or dead, so no rewrite applies at all.
+        assert!(Transformer::optimize("let m = 1;\nconst n = m;\nlet y = n;\n").is_none());
+    }
+
+    #[test]
+    fn never_inlines_a_name_shadowed_by_a_destructured_parameter() {
+        // The `n` inside `f` is a fresh binding from the `{n}` parameter pattern, not
+        // a use of the outer `const n`, so inlining `42` into `return n;` would change
+        // `f`'s behavior. No other rewrite applies, so this must stay untouched.
+        let src = "const n = 42;\nfunction f({n}) {\n  return n;\n}\n";
+        assert!(Transformer::optimize(src).is_none());
+    }
+
+    #[test]
+    fn returns_none_when_no_rewrite_applies() {
+        assert!(Transformer::optimize("let x = f();\n").is_none());
+    }
+}